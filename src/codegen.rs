@@ -0,0 +1,297 @@
+use interpreter::Interpreter;
+use parser::BinaryOperator;
+use parser::Declaration;
+use parser::Expression;
+use parser::Statement;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+/// Failures that can occur while lowering an already-parsed program to IR.
+#[derive(Debug, PartialEq)]
+pub enum CodegenError {
+  /// A name had no `alloca` on file when it was loaded from or stored to,
+  /// i.e. it was read or assigned before a `let`/parameter defined it.
+  UnknownIdentifier(String),
+
+  /// A construct that could not be lowered, such as a numeric literal
+  /// whose text does not parse.
+  Unsupported(String),
+}
+
+/// Lowers a parsed program into a textual, LLVM-flavored IR, one
+/// `Declaration::Function` at a time. Every `vampc` numeric value becomes
+/// a `double`; `let`-bound variables are backed by an `alloca` so
+/// reassignment is a plain `store`.
+///
+/// This is a hand-rolled IR printer rather than a binding to a real LLVM
+/// (`vampc` has no package manifest and is built with a bare `rustc`, so it
+/// cannot depend on an external crate like `inkwell`); it emits assembly
+/// that reads like `llc`-compatible IR without actually linking LLVM.
+///
+/// The lowering is `double`-only; nothing is lowered to `i32`. `Equality`
+/// yields `0.0`/`1.0` (an `fcmp` followed by `uitofp` in
+/// `compile_expression`), where the interpreter yields a `Value::Bool`. An
+/// `If` condition is branched on by comparing that double against `0.0`, so
+/// any non-zero number counts as true, whereas the interpreter rejects a
+/// condition that is not a bool.
+pub struct Codegen {
+  module_name: String,
+  ir: String,
+  variables: HashMap<String, String>,
+  next_temp: u32,
+  next_label: u32,
+}
+
+impl Codegen {
+  pub fn new(module_name: &str) -> Codegen {
+    Codegen {
+      module_name: module_name.to_string(),
+      ir: String::new(),
+      variables: HashMap::new(),
+      next_temp: 0,
+      next_label: 0,
+    }
+  }
+
+  /// Lowers every `Declaration::Function` in `declarations` into the
+  /// module, returning the generated IR as text.
+  pub fn compile(
+    &mut self,
+    declarations: &[Declaration],
+  ) -> Result<String, CodegenError> {
+    let _ = writeln!(self.ir, "; ModuleID = '{}'", self.module_name);
+    for declaration in declarations {
+      if let Declaration::Function { name, params, body } = declaration {
+        self.compile_function(name, params, body)?;
+      }
+    }
+    Ok(self.ir.clone())
+  }
+
+  fn compile_function(
+    &mut self,
+    name: &str,
+    params: &[String],
+    body: &[Statement],
+  ) -> Result<(), CodegenError> {
+    self.variables.clear();
+    self.next_temp = 0;
+    self.next_label = 0;
+
+    let param_list = params
+      .iter()
+      .map(|param| format!("double %{}", param))
+      .collect::<Vec<_>>()
+      .join(", ");
+    let _ = writeln!(self.ir, "define double @{}({}) {{", name, param_list);
+    let _ = writeln!(self.ir, "entry:");
+
+    for param in params {
+      let alloca = self.fresh_temp();
+      let _ = writeln!(self.ir, "  {} = alloca double", alloca);
+      let _ = writeln!(self.ir, "  store double %{}, double* {}", param, alloca);
+      self.variables.insert(param.clone(), alloca);
+    }
+
+    let returned = self.compile_block(body)?;
+    if !returned {
+      let _ = writeln!(self.ir, "  ret double 0.0");
+    }
+    let _ = writeln!(self.ir, "}}");
+    Ok(())
+  }
+
+  /// Compiles a single statement, returning `true` if it returns on every
+  /// path (a `return`, or an `if` whose branches both return), so the caller
+  /// stops lowering the statements that follow it in the block.
+  fn compile_statement(
+    &mut self,
+    statement: &Statement,
+  ) -> Result<bool, CodegenError> {
+    match statement {
+      Statement::Comment { .. } => Ok(false),
+      Statement::Expression { expression } => {
+        self.compile_expression(expression)?;
+        Ok(false)
+      }
+      Statement::Variable { name, value } => {
+        let value = match value {
+          Some(expression) => self.compile_expression(expression)?,
+          None => String::from("0.0"),
+        };
+        let alloca = self.fresh_temp();
+        let _ = writeln!(self.ir, "  {} = alloca double", alloca);
+        let _ = writeln!(self.ir, "  store double {}, double* {}", value, alloca);
+        self.variables.insert(name.clone(), alloca);
+        Ok(false)
+      }
+      Statement::Return { value } => {
+        match value {
+          Some(expression) => {
+            let value = self.compile_expression(expression)?;
+            let _ = writeln!(self.ir, "  ret double {}", value);
+          }
+          None => {
+            let _ = writeln!(self.ir, "  ret double 0.0");
+          }
+        }
+        Ok(true)
+      }
+      Statement::If {
+        condition,
+        then_body,
+        else_body,
+      } => self.compile_if(condition, then_body, else_body.as_deref()),
+    }
+  }
+
+  /// Lowers an `if`/`else` as a `double`-vs-`0.0` branch into `then`/`else`
+  /// blocks that jump to a shared merge block, unless a branch already
+  /// returned (in which case it falls through to its `ret` instead).
+  fn compile_if(
+    &mut self,
+    condition: &Expression,
+    then_body: &[Statement],
+    else_body: Option<&[Statement]>,
+  ) -> Result<bool, CodegenError> {
+    let condition = self.compile_expression(condition)?;
+    let is_true = self.fresh_temp();
+    let _ = writeln!(
+      self.ir,
+      "  {} = fcmp one double {}, 0.0",
+      is_true, condition
+    );
+
+    let then_label = self.fresh_label("if.then");
+    let else_label = self.fresh_label("if.else");
+    let merge_label = self.fresh_label("if.end");
+    let _ = writeln!(
+      self.ir,
+      "  br i1 {}, label %{}, label %{}",
+      is_true, then_label, else_label
+    );
+
+    let _ = writeln!(self.ir, "{}:", then_label);
+    let then_returned = self.compile_block(then_body)?;
+    if !then_returned {
+      let _ = writeln!(self.ir, "  br label %{}", merge_label);
+    }
+
+    let _ = writeln!(self.ir, "{}:", else_label);
+    let else_returned = match else_body {
+      Some(else_body) => self.compile_block(else_body)?,
+      None => false,
+    };
+    if !else_returned {
+      let _ = writeln!(self.ir, "  br label %{}", merge_label);
+    }
+
+    if then_returned && else_returned {
+      Ok(true)
+    } else {
+      let _ = writeln!(self.ir, "{}:", merge_label);
+      Ok(false)
+    }
+  }
+
+  /// Compiles a block, returning `true` if it returned on every path (so a
+  /// caller lowering an enclosing block knows not to fall through to it).
+  fn compile_block(
+    &mut self,
+    statements: &[Statement],
+  ) -> Result<bool, CodegenError> {
+    for statement in statements {
+      if self.compile_statement(statement)? {
+        return Ok(true);
+      }
+    }
+    Ok(false)
+  }
+
+  /// Compiles `expression`, returning the IR operand (a `%temp` register or
+  /// a literal) holding its value.
+  fn compile_expression(
+    &mut self,
+    expression: &Expression,
+  ) -> Result<String, CodegenError> {
+    match expression {
+      Expression::Numeric { value } => {
+        let parsed = Interpreter::parse_numeric(value).map_err(|_| {
+          CodegenError::Unsupported(format!("Invalid numeric literal: {}", value))
+        })?;
+        Ok(format!("{:?}", parsed))
+      }
+      Expression::Identifier { name } => {
+        let alloca = self.lookup(name)?;
+        let temp = self.fresh_temp();
+        let _ = writeln!(self.ir, "  {} = load double, double* {}", temp, alloca);
+        Ok(temp)
+      }
+      Expression::Assignment { name, value } => {
+        let value = self.compile_expression(value)?;
+        let alloca = self.lookup(name)?;
+        let _ = writeln!(self.ir, "  store double {}, double* {}", value, alloca);
+        Ok(value)
+      }
+      Expression::Binary {
+        left,
+        right,
+        operator,
+      } => {
+        let left = self.compile_expression(left)?;
+        let right = self.compile_expression(right)?;
+        let temp = self.fresh_temp();
+        match operator {
+          BinaryOperator::Addition => {
+            let _ = writeln!(
+              self.ir,
+              "  {} = fadd double {}, {}",
+              temp, left, right
+            );
+          }
+          BinaryOperator::Subtraction => {
+            let _ = writeln!(
+              self.ir,
+              "  {} = fsub double {}, {}",
+              temp, left, right
+            );
+          }
+          BinaryOperator::Equality => {
+            let cmp = self.fresh_temp();
+            let _ = writeln!(
+              self.ir,
+              "  {} = fcmp oeq double {}, {}",
+              cmp, left, right
+            );
+            let _ = writeln!(
+              self.ir,
+              "  {} = uitofp i1 {} to double",
+              temp, cmp
+            );
+          }
+        }
+        Ok(temp)
+      }
+    }
+  }
+
+  fn lookup(&self, name: &str) -> Result<String, CodegenError> {
+    self
+      .variables
+      .get(name)
+      .cloned()
+      .ok_or_else(|| CodegenError::UnknownIdentifier(name.to_string()))
+  }
+
+  fn fresh_temp(&mut self) -> String {
+    let temp = format!("%t{}", self.next_temp);
+    self.next_temp += 1;
+    temp
+  }
+
+  fn fresh_label(&mut self, prefix: &str) -> String {
+    let label = format!("{}{}", prefix, self.next_label);
+    self.next_label += 1;
+    label
+  }
+}
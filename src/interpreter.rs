@@ -0,0 +1,440 @@
+use parser::BinaryOperator;
+use parser::Declaration;
+use parser::Expression;
+use parser::Statement;
+use std::collections::HashMap;
+
+/// Tree-walks a parsed program, executing its `main` function.
+#[derive(Debug)]
+pub struct Interpreter {
+  environment: Environment,
+}
+
+/// Runtime values a `vampc` program can produce.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+  Bool(bool),
+  Number(f64),
+}
+
+/// Failures that can occur while evaluating an already-parsed program.
+#[derive(Debug, PartialEq)]
+pub enum RuntimeError {
+  /// An identifier was read or assigned before it was ever defined.
+  UnknownIdentifier(String),
+
+  /// An operation was applied to value(s) it doesn't support.
+  TypeMismatch(String),
+}
+
+/// The outcome of executing a statement or block of statements: either it
+/// ran to completion (optionally producing a value, for the last
+/// expression statement of a REPL line), or it hit a `return` and the
+/// enclosing block should stop executing.
+enum Flow {
+  Normal(Option<Value>),
+  Return(Option<Value>),
+}
+
+/// The variable bindings visible to a running program.
+///
+/// There's only one scope: `vampc` has no block scoping, so a `let` inside
+/// an `if` body defines a name that's visible (and reassignable) for the
+/// rest of the enclosing function, same as a top-level `let`.
+#[derive(Debug)]
+struct Environment {
+  variables: HashMap<String, Value>,
+}
+
+impl Environment {
+  fn new() -> Environment {
+    Environment {
+      variables: HashMap::new(),
+    }
+  }
+
+  fn define(&mut self, name: String, value: Value) {
+    self.variables.insert(name, value);
+  }
+
+  fn get(&self, name: &str) -> Option<Value> {
+    self.variables.get(name).cloned()
+  }
+
+  fn assign(&mut self, name: &str, value: Value) -> Result<(), RuntimeError> {
+    if self.variables.contains_key(name) {
+      self.variables.insert(name.to_string(), value);
+      Ok(())
+    } else {
+      Err(RuntimeError::UnknownIdentifier(name.to_string()))
+    }
+  }
+}
+
+impl Interpreter {
+  pub fn new() -> Interpreter {
+    Interpreter {
+      environment: Environment::new(),
+    }
+  }
+
+  /// Finds the `main` function among `declarations` and executes its body.
+  /// Programs without a `main` function simply do nothing.
+  pub fn run(
+    &mut self,
+    declarations: &[Declaration],
+  ) -> Result<(), RuntimeError> {
+    let main = declarations.iter().find_map(|declaration| {
+      match declaration {
+        Declaration::Function { name, body, .. } if name == "main" => {
+          Some(body)
+        }
+        _ => None,
+      }
+    });
+    match main {
+      Some(body) => {
+        self.eval_block(body)?;
+        Ok(())
+      }
+      None => Ok(()),
+    }
+  }
+
+  /// Evaluates a bare sequence of statements (as produced by
+  /// `Parser::parse_statements` for a REPL line), returning the value of
+  /// the final statement if it is an expression statement (or a
+  /// `return`ed value), if any.
+  pub fn eval_statements(
+    &mut self,
+    statements: &[Statement],
+  ) -> Result<Option<Value>, RuntimeError> {
+    match self.eval_block(statements)? {
+      Flow::Normal(value) | Flow::Return(value) => Ok(value),
+    }
+  }
+
+  fn eval_block(
+    &mut self,
+    statements: &[Statement],
+  ) -> Result<Flow, RuntimeError> {
+    let mut flow = Flow::Normal(None);
+    for statement in statements {
+      flow = self.eval_statement(statement)?;
+      if let Flow::Return(_) = flow {
+        return Ok(flow);
+      }
+    }
+    Ok(flow)
+  }
+
+  fn eval_statement(
+    &mut self,
+    statement: &Statement,
+  ) -> Result<Flow, RuntimeError> {
+    match statement {
+      Statement::Comment { .. } => Ok(Flow::Normal(None)),
+      Statement::Expression { expression } => {
+        Ok(Flow::Normal(Some(self.eval_expression(expression)?)))
+      }
+      Statement::Variable { name, value } => {
+        let value = match value {
+          Some(expression) => self.eval_expression(expression)?,
+          None => Value::Bool(false),
+        };
+        self.environment.define(name.clone(), value);
+        Ok(Flow::Normal(None))
+      }
+      Statement::Return { value } => {
+        let value = match value {
+          Some(expression) => Some(self.eval_expression(expression)?),
+          None => None,
+        };
+        Ok(Flow::Return(value))
+      }
+      Statement::If {
+        condition,
+        then_body,
+        else_body,
+      } => match self.eval_expression(condition)? {
+        Value::Bool(true) => self.eval_block(then_body),
+        Value::Bool(false) => match else_body {
+          Some(else_body) => self.eval_block(else_body),
+          None => Ok(Flow::Normal(None)),
+        },
+        other => Err(RuntimeError::TypeMismatch(format!(
+          "Expected a bool condition, got {:?}",
+          other
+        ))),
+      },
+    }
+  }
+
+  fn eval_expression(
+    &mut self,
+    expression: &Expression,
+  ) -> Result<Value, RuntimeError> {
+    match expression {
+      Expression::Numeric { value } => {
+        Interpreter::parse_numeric(value).map(Value::Number)
+      }
+      Expression::Identifier { name } => self
+        .environment
+        .get(name)
+        .ok_or_else(|| RuntimeError::UnknownIdentifier(name.clone())),
+      Expression::Assignment { name, value } => {
+        let value = self.eval_expression(value)?;
+        self.environment.assign(name, value.clone())?;
+        Ok(value)
+      }
+      Expression::Binary {
+        left,
+        right,
+        operator,
+      } => {
+        let left = self.eval_expression(left)?;
+        let right = self.eval_expression(right)?;
+        Interpreter::eval_binary(operator, left, right)
+      }
+    }
+  }
+
+  fn eval_binary(
+    operator: &BinaryOperator,
+    left: Value,
+    right: Value,
+  ) -> Result<Value, RuntimeError> {
+    match operator {
+      BinaryOperator::Equality => Ok(Value::Bool(left == right)),
+      BinaryOperator::Addition => match (left, right) {
+        (Value::Number(a), Value::Number(b)) => Ok(Value::Number(a + b)),
+        (a, b) => Err(RuntimeError::TypeMismatch(format!(
+          "Cannot add {:?} and {:?}",
+          a, b
+        ))),
+      },
+      BinaryOperator::Subtraction => match (left, right) {
+        (Value::Number(a), Value::Number(b)) => Ok(Value::Number(a - b)),
+        (a, b) => Err(RuntimeError::TypeMismatch(format!(
+          "Cannot subtract {:?} and {:?}",
+          a, b
+        ))),
+      },
+    }
+  }
+
+  /// Parses a `Numeric` literal's source text, honoring the `0x`/`0b`
+  /// radix prefixes `Scanner::scan_number` recognizes.
+  ///
+  /// `pub(crate)` so `codegen` can lower the same literal text the same
+  /// way and the two backends never disagree on a program's meaning.
+  pub(crate) fn parse_numeric(value: &str) -> Result<f64, RuntimeError> {
+    let parsed = if let Some(digits) =
+      value.strip_prefix("0x").or_else(|| value.strip_prefix("0X"))
+    {
+      i64::from_str_radix(digits, 16).map(|n| n as f64)
+    } else if let Some(digits) =
+      value.strip_prefix("0b").or_else(|| value.strip_prefix("0B"))
+    {
+      i64::from_str_radix(digits, 2).map(|n| n as f64)
+    } else {
+      return value.parse::<f64>().map_err(|_| {
+        RuntimeError::TypeMismatch(format!(
+          "Invalid numeric literal: {}",
+          value
+        ))
+      });
+    };
+    parsed.map_err(|_| {
+      RuntimeError::TypeMismatch(format!("Invalid numeric literal: {}", value))
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn numeric(value: &str) -> Expression {
+    Expression::Numeric {
+      value: String::from(value),
+    }
+  }
+
+  fn run(body: Vec<Statement>) -> Result<Interpreter, RuntimeError> {
+    let mut interpreter = Interpreter::new();
+    interpreter.run(&[Declaration::Function {
+      name: String::from("main"),
+      params: Vec::new(),
+      body,
+    }])?;
+    Ok(interpreter)
+  }
+
+  #[test]
+  fn test_variable_binding() {
+    let interpreter = run(vec![Statement::Variable {
+      name: String::from("x"),
+      value: Some(numeric("1")),
+    }])
+    .unwrap();
+    assert_eq!(interpreter.environment.get("x"), Some(Value::Number(1.0)));
+  }
+
+  #[test]
+  fn test_assignment_rebinds() {
+    let interpreter = run(vec![
+      Statement::Variable {
+        name: String::from("x"),
+        value: Some(numeric("1")),
+      },
+      Statement::Expression {
+        expression: Expression::Assignment {
+          name: String::from("x"),
+          value: Box::new(numeric("2")),
+        },
+      },
+    ])
+    .unwrap();
+    assert_eq!(interpreter.environment.get("x"), Some(Value::Number(2.0)));
+  }
+
+  #[test]
+  fn test_addition() {
+    let interpreter = run(vec![Statement::Variable {
+      name: String::from("x"),
+      value: Some(Expression::Binary {
+        left: Box::new(numeric("1")),
+        right: Box::new(numeric("2")),
+        operator: BinaryOperator::Addition,
+      }),
+    }])
+    .unwrap();
+    assert_eq!(interpreter.environment.get("x"), Some(Value::Number(3.0)));
+  }
+
+  #[test]
+  fn test_equality() {
+    let interpreter = run(vec![Statement::Variable {
+      name: String::from("x"),
+      value: Some(Expression::Binary {
+        left: Box::new(numeric("1")),
+        right: Box::new(numeric("1")),
+        operator: BinaryOperator::Equality,
+      }),
+    }])
+    .unwrap();
+    assert_eq!(interpreter.environment.get("x"), Some(Value::Bool(true)));
+  }
+
+  #[test]
+  fn test_hex_and_binary_literals() {
+    let interpreter = run(vec![
+      Statement::Variable {
+        name: String::from("a"),
+        value: Some(numeric("0xFF")),
+      },
+      Statement::Variable {
+        name: String::from("b"),
+        value: Some(numeric("0b1010")),
+      },
+    ])
+    .unwrap();
+    assert_eq!(interpreter.environment.get("a"), Some(Value::Number(255.0)));
+    assert_eq!(interpreter.environment.get("b"), Some(Value::Number(10.0)));
+  }
+
+  #[test]
+  fn test_unknown_identifier() {
+    let error = run(vec![Statement::Expression {
+      expression: Expression::Identifier {
+        name: String::from("missing"),
+      },
+    }])
+    .unwrap_err();
+    assert_eq!(
+      error,
+      RuntimeError::UnknownIdentifier(String::from("missing"))
+    );
+  }
+
+  #[test]
+  fn test_if_true_runs_then_body() {
+    let interpreter = run(vec![Statement::If {
+      condition: Expression::Binary {
+        left: Box::new(numeric("1")),
+        right: Box::new(numeric("1")),
+        operator: BinaryOperator::Equality,
+      },
+      then_body: vec![Statement::Variable {
+        name: String::from("x"),
+        value: Some(numeric("1")),
+      }],
+      else_body: None,
+    }])
+    .unwrap();
+    assert_eq!(interpreter.environment.get("x"), Some(Value::Number(1.0)));
+  }
+
+  #[test]
+  fn test_if_false_skips_then_body() {
+    let interpreter = run(vec![Statement::If {
+      condition: Expression::Binary {
+        left: Box::new(numeric("1")),
+        right: Box::new(numeric("2")),
+        operator: BinaryOperator::Equality,
+      },
+      then_body: vec![Statement::Variable {
+        name: String::from("x"),
+        value: Some(numeric("1")),
+      }],
+      else_body: None,
+    }])
+    .unwrap();
+    assert_eq!(interpreter.environment.get("x"), None);
+  }
+
+  #[test]
+  fn test_if_condition_must_be_bool() {
+    let error = run(vec![Statement::If {
+      condition: numeric("1"),
+      then_body: vec![],
+      else_body: None,
+    }])
+    .unwrap_err();
+    match error {
+      RuntimeError::TypeMismatch(_) => {}
+      other => panic!("Expected a TypeMismatch error, got {:?}", other),
+    }
+  }
+
+  #[test]
+  fn test_return_stops_block_early() {
+    let mut interpreter = Interpreter::new();
+    let value = interpreter
+      .run(&[Declaration::Function {
+        name: String::from("main"),
+        params: Vec::new(),
+        body: vec![
+          Statement::Return {
+            value: Some(numeric("1")),
+          },
+          Statement::Variable {
+            name: String::from("x"),
+            value: Some(numeric("2")),
+          },
+        ],
+      }])
+      .map(|()| interpreter.environment.get("x"));
+    assert_eq!(value, Ok(None));
+  }
+
+  #[test]
+  fn test_no_main_is_a_no_op() {
+    let mut interpreter = Interpreter::new();
+    let declarations = vec![Declaration::Comment {
+      text: String::from("no main here"),
+    }];
+    assert_eq!(interpreter.run(&declarations), Ok(()));
+  }
+}
@@ -1,51 +1,164 @@
 use std::env;
 use std::fs;
+use std::io;
+use std::io::Write;
 
-#[allow(dead_code)]
+mod codegen;
+mod interpreter;
 mod parser;
 mod scanner;
 
+/// CLI flags that let a user introspect each compilation stage.
+struct Flags {
+  /// Dumps `Scanner::output` before parsing.
+  show_tokens: bool,
+
+  /// Dumps the parsed AST before evaluating.
+  show_parse: bool,
+
+  /// Lowers a script to LLVM IR and prints it instead of interpreting it.
+  /// The REPL ignores this flag and always interprets.
+  compile: bool,
+}
+
 /// Entrypoint into the compiler/interpreter/virtual machine, what have it.
 ///
 /// # Usage
 ///
 /// ```sh
-/// vampc <script>
+/// vampc [--show-tokens] [--show-parse] [--compile] [<script>]
 /// ```
 fn main() {
-  let args: Vec<String> = env::args().collect();
-  match args.len() - 1 {
-    0 => {
-      println!("REPL not yet implemented");
-      std::process::exit(1);
-    }
-    1 => {
-      let file = &args[1];
-      load_and_run(file);
+  let mut flags = Flags {
+    show_tokens: false,
+    show_parse: false,
+    compile: false,
+  };
+  let mut positional: Vec<String> = Vec::new();
+  for arg in env::args().skip(1) {
+    match arg.as_str() {
+      "--show-tokens" => flags.show_tokens = true,
+      "--show-parse" => flags.show_parse = true,
+      "--compile" => flags.compile = true,
+      _ => positional.push(arg),
     }
+  }
+  match positional.len() {
+    0 => repl(&flags),
+    1 => load_and_run(&positional[0], &flags),
     _ => {
-      println!("Usage: vampc <script>");
+      println!(
+        "Usage: vampc [--show-tokens] [--show-parse] [--compile] [<script>]"
+      );
       std::process::exit(1);
     }
   }
 }
 
-/// Parses and runs a program from source.
+/// Parses and runs (or, with `--compile`, lowers to LLVM IR) a program from
+/// source.
 ///
 /// Currently only a single file program is supported.
-fn run_source(source: String) {
+fn run_source(source: String, flags: &Flags) {
   let mut scanner = scanner::Scanner::new(source);
   scanner.scan();
+  if flags.show_tokens {
+    for token in &scanner.output {
+      println!("{:?}", token);
+    }
+  }
   let mut parser = parser::Parser::new(scanner.output);
   parser.parse();
-  for ast in parser.output {
-    println!("{:?}", ast);
+  if flags.show_parse {
+    for declaration in &parser.output {
+      println!("{:?}", declaration);
+    }
+  }
+  if flags.compile {
+    compile_and_print(&parser.output);
+  } else {
+    let mut interpreter = interpreter::Interpreter::new();
+    if let Err(error) = interpreter.run(&parser.output) {
+      eprintln!("Runtime error: {:?}", error);
+      std::process::exit(1);
+    }
+  }
+}
+
+/// Lowers `declarations` to LLVM IR and prints it for inspection.
+fn compile_and_print(declarations: &[parser::Declaration]) {
+  let mut codegen = codegen::Codegen::new("vampc");
+  match codegen.compile(declarations) {
+    Ok(ir) => println!("{}", ir),
+    Err(error) => {
+      eprintln!("Codegen error: {:?}", error);
+      std::process::exit(1);
+    }
   }
 }
 
 /// Loads a file and passes it to `run_source`.
-fn load_and_run(input: &str) {
+fn load_and_run(input: &str, flags: &Flags) {
   let result = fs::read_to_string(input);
   let contents = result.expect("Could not read file");
-  run_source(contents);
+  run_source(contents, flags);
+}
+
+/// Runs an interactive read-eval-print loop.
+///
+/// Unlike `run_source`, REPL input is a bare sequence of statements (no
+/// surrounding `func main`), and the interpreter's variable environment
+/// persists across lines, so `let x = 1` followed by `x + 2` works.
+///
+/// The parser still `panic!`s on malformed input (e.g. an unclosed paren),
+/// so a single bad line is isolated with `catch_unwind` rather than being
+/// allowed to tear down the whole session and its persistent environment.
+fn repl(flags: &Flags) {
+  std::panic::set_hook(Box::new(|info| {
+    let message = info
+      .payload()
+      .downcast_ref::<&str>()
+      .map(|s| s.to_string())
+      .or_else(|| info.payload().downcast_ref::<String>().cloned())
+      .unwrap_or_else(|| String::from("could not parse line"));
+    eprintln!("Parse error: {}", message);
+  }));
+
+  let mut interpreter = interpreter::Interpreter::new();
+  let stdin = io::stdin();
+  loop {
+    print!("> ");
+    io::stdout().flush().expect("Could not flush stdout");
+
+    let mut line = String::new();
+    if stdin.read_line(&mut line).expect("Could not read line") == 0 {
+      break;
+    }
+
+    let mut scanner = scanner::Scanner::new(line);
+    scanner.scan();
+    if flags.show_tokens {
+      for token in &scanner.output {
+        println!("{:?}", token);
+      }
+    }
+
+    let tokens = scanner.output;
+    let statements =
+      match std::panic::catch_unwind(move || parser::Parser::parse_statements(tokens)) {
+        Ok(statements) => statements,
+        Err(_) => continue,
+      };
+    if flags.show_parse {
+      for statement in &statements {
+        println!("{:?}", statement);
+      }
+    }
+
+    match interpreter.eval_statements(&statements) {
+      Ok(Some(value)) => println!("{:?}", value),
+      Ok(None) => println!("{:?}", statements),
+      Err(error) => eprintln!("Runtime error: {:?}", error),
+    }
+  }
 }
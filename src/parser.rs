@@ -1,7 +1,9 @@
 use scanner::Keyword;
+use scanner::OperatorSymbol;
 use scanner::PairSymbol;
 use scanner::PairType;
 use scanner::Token;
+use scanner::TokenKind;
 use std::iter;
 
 /// Represents an architecture for parsing tokens into an AST (tree).
@@ -14,8 +16,14 @@ pub struct Parser {
 /// Known top-level declarations.
 #[derive(Debug, PartialEq)]
 pub enum Declaration {
-  Comment { text: String },
-  Function { name: String, body: Vec<Statement> },
+  Comment {
+    text: String,
+  },
+  Function {
+    name: String,
+    params: Vec<String>,
+    body: Vec<Statement>,
+  },
 }
 
 /// Known statements.
@@ -27,6 +35,14 @@ pub enum Statement {
   Expression {
     expression: Expression,
   },
+  If {
+    condition: Expression,
+    then_body: Vec<Statement>,
+    else_body: Option<Vec<Statement>>,
+  },
+  Return {
+    value: Option<Expression>,
+  },
   Variable {
     name: String,
     value: Option<Expression>,
@@ -45,6 +61,12 @@ pub enum Expression {
     right: Box<Expression>,
     operator: BinaryOperator,
   },
+  Identifier {
+    name: String,
+  },
+  Numeric {
+    value: String,
+  },
 }
 
 /// Known binary operators.
@@ -63,14 +85,29 @@ impl Parser {
     }
   }
 
+  /// Parses `tokens` as a bare sequence of statements, rather than a full
+  /// program of top-level declarations. Used by the REPL, where each line
+  /// is a statement (or expression) rather than a `func` declaration.
+  pub fn parse_statements(tokens: Vec<Token>) -> Vec<Statement> {
+    let mut tokens = tokens.iter().peekable();
+    let mut statements = Vec::new();
+    while tokens.peek().is_some() {
+      match Parser::parse_statement(&mut tokens) {
+        Some(statement) => statements.push(statement),
+        None => break,
+      }
+    }
+    statements
+  }
+
   pub fn parse(&mut self) {
     let mut tokens = self.input.iter().peekable();
     while let Some(next) = tokens.next() {
-      let declaration: Option<Declaration> = match next {
-        Token::Comment(comment) => Some(Declaration::Comment {
+      let declaration: Option<Declaration> = match &next.kind {
+        TokenKind::Comment(comment) => Some(Declaration::Comment {
           text: Parser::parse_comment_contents(comment, &mut tokens),
         }),
-        Token::Keyword(keyword) => match keyword {
+        TokenKind::Keyword(keyword) => match keyword {
           Keyword::Func => {
             Some(Parser::parse_function_declaration(&mut tokens))
           }
@@ -89,8 +126,10 @@ impl Parser {
     tokens: &mut iter::Peekable<T>,
   ) -> String {
     let mut buffer = String::from(initial);
-    while let Some(Token::Comment(comment)) = tokens.peek() {
-      buffer.push_str("\n");
+    while let Some(TokenKind::Comment(comment)) =
+      tokens.peek().map(|token| &token.kind)
+    {
+      buffer.push('\n');
       buffer.push_str(comment);
       tokens.next();
     }
@@ -100,36 +139,68 @@ impl Parser {
   fn parse_function_declaration<'a, T: Iterator<Item = &'a Token>>(
     tokens: &mut iter::Peekable<T>,
   ) -> Declaration {
-    if let Some(Token::Identifier(name)) = tokens.peek() {
+    if let Some(TokenKind::Identifier(name)) =
+      tokens.peek().map(|token| &token.kind)
+    {
+      let name = name.to_string();
       tokens.next();
+      let params = Parser::parse_parameter_list(tokens);
       let body = Parser::parse_statement_body(tokens);
-      Declaration::Function {
-        name: name.to_string(),
-        body,
-      }
+      Declaration::Function { name, params, body }
     } else {
       panic!("Expected Identifier");
     }
   }
 
+  fn parse_parameter_list<'a, T: Iterator<Item = &'a Token>>(
+    tokens: &mut iter::Peekable<T>,
+  ) -> Vec<String> {
+    match tokens.peek().map(|token| &token.kind) {
+      Some(TokenKind::Pair(PairSymbol::Parentheses, PairType::Open)) => {
+        tokens.next();
+      }
+      _ => panic!("Expected ("),
+    }
+    let mut params = Vec::new();
+    loop {
+      match tokens.peek().map(|token| &token.kind) {
+        Some(TokenKind::Pair(PairSymbol::Parentheses, PairType::Close)) => {
+          tokens.next();
+          break;
+        }
+        Some(TokenKind::Identifier(name)) => {
+          params.push(name.to_string());
+          tokens.next();
+          if let Some(TokenKind::Comma) =
+            tokens.peek().map(|token| &token.kind)
+          {
+            tokens.next();
+          }
+        }
+        _ => panic!("Expected )"),
+      }
+    }
+    params
+  }
+
   fn parse_statement_body<'a, T: Iterator<Item = &'a Token>>(
     tokens: &mut iter::Peekable<T>,
   ) -> Vec<Statement> {
     let mut statements = Vec::<Statement>::new();
-    match tokens.peek() {
-      Some(Token::Pair(PairSymbol::CurlyBracket, PairType::Open)) => {
+    match tokens.peek().map(|token| &token.kind) {
+      Some(TokenKind::Pair(PairSymbol::CurlyBracket, PairType::Open)) => {
         tokens.next();
       }
-      _ => panic!("Expected {"),
+      _ => panic!("{}", "Expected {"),
     }
     loop {
-      match tokens.peek() {
-        Some(Token::Pair(PairSymbol::CurlyBracket, PairType::Close)) => {
+      match tokens.peek().map(|token| &token.kind) {
+        Some(TokenKind::Pair(PairSymbol::CurlyBracket, PairType::Close)) => {
           tokens.next();
           break;
         }
         None => {
-          panic!("Expected }");
+          panic!("{}", "Expected }");
         }
         _ => {
           if let Some(statement) = Parser::parse_statement(tokens) {
@@ -146,17 +217,172 @@ impl Parser {
   fn parse_statement<'a, T: Iterator<Item = &'a Token>>(
     tokens: &mut iter::Peekable<T>,
   ) -> Option<Statement> {
-    if let Some(expression) = Parser::parse_expression(tokens) {
-      Some(Statement::Expression { expression })
+    if let Some(TokenKind::Comment(_)) = tokens.peek().map(|token| &token.kind)
+    {
+      let comment = match &tokens.next().unwrap().kind {
+        TokenKind::Comment(comment) => comment,
+        _ => unreachable!(),
+      };
+      return Some(Statement::Comment {
+        text: Parser::parse_comment_contents(comment, tokens),
+      });
+    }
+    match tokens.peek().map(|token| &token.kind) {
+      Some(TokenKind::Keyword(Keyword::Let)) => {
+        tokens.next();
+        Some(Parser::parse_variable_declaration(tokens))
+      }
+      Some(TokenKind::Keyword(Keyword::Return)) => {
+        tokens.next();
+        Some(Parser::parse_return_statement(tokens))
+      }
+      Some(TokenKind::Keyword(Keyword::If)) => {
+        tokens.next();
+        Some(Parser::parse_if_statement(tokens))
+      }
+      _ => Parser::parse_expression(tokens)
+        .map(|expression| Statement::Expression { expression }),
+    }
+  }
+
+  fn parse_return_statement<'a, T: Iterator<Item = &'a Token>>(
+    tokens: &mut iter::Peekable<T>,
+  ) -> Statement {
+    let value = match tokens.peek().map(|token| &token.kind) {
+      Some(TokenKind::Pair(PairSymbol::CurlyBracket, PairType::Close))
+      | None => None,
+      _ => Parser::parse_expression(tokens),
+    };
+    Statement::Return { value }
+  }
+
+  fn parse_if_statement<'a, T: Iterator<Item = &'a Token>>(
+    tokens: &mut iter::Peekable<T>,
+  ) -> Statement {
+    let condition = match Parser::parse_expression(tokens) {
+      Some(condition) => condition,
+      None => panic!("Expected condition after 'if'"),
+    };
+    let then_body = Parser::parse_statement_body(tokens);
+    let else_body = match tokens.peek().map(|token| &token.kind) {
+      Some(TokenKind::Keyword(Keyword::Else)) => {
+        tokens.next();
+        Some(Parser::parse_statement_body(tokens))
+      }
+      _ => None,
+    };
+    Statement::If {
+      condition,
+      then_body,
+      else_body,
+    }
+  }
+
+  fn parse_variable_declaration<'a, T: Iterator<Item = &'a Token>>(
+    tokens: &mut iter::Peekable<T>,
+  ) -> Statement {
+    if let Some(TokenKind::Identifier(name)) =
+      tokens.next().map(|token| &token.kind)
+    {
+      let name = name.to_string();
+      let value = match tokens.peek().map(|token| &token.kind) {
+        Some(TokenKind::Operator(OperatorSymbol::Assignment)) => {
+          tokens.next();
+          Parser::parse_expression(tokens)
+        }
+        _ => None,
+      };
+      Statement::Variable { name, value }
     } else {
-      None
+      panic!("Expected Identifier");
     }
   }
 
+  /// Parses a single expression using precedence climbing (a Pratt parser).
   fn parse_expression<'a, T: Iterator<Item = &'a Token>>(
-    _tokens: &mut iter::Peekable<T>,
+    tokens: &mut iter::Peekable<T>,
   ) -> Option<Expression> {
-    None
+    Parser::parse_expression_bp(tokens, 0)
+  }
+
+  fn parse_expression_bp<'a, T: Iterator<Item = &'a Token>>(
+    tokens: &mut iter::Peekable<T>,
+    min_bp: u8,
+  ) -> Option<Expression> {
+    let mut left = Parser::parse_prefix(tokens)?;
+    while let Some(TokenKind::Operator(symbol)) =
+      tokens.peek().map(|token| &token.kind)
+    {
+      let (operator, left_bp, right_bp) = match Parser::binding_power(symbol) {
+        Some(result) => result,
+        None => break,
+      };
+      if left_bp < min_bp {
+        break;
+      }
+      tokens.next();
+      let right = Parser::parse_expression_bp(tokens, right_bp)?;
+      left = Expression::Binary {
+        left: Box::new(left),
+        right: Box::new(right),
+        operator,
+      };
+    }
+    Some(left)
+  }
+
+  /// Parses a prefix (primary) expression: a literal, identifier, assignment,
+  /// or parenthesized sub-expression.
+  fn parse_prefix<'a, T: Iterator<Item = &'a Token>>(
+    tokens: &mut iter::Peekable<T>,
+  ) -> Option<Expression> {
+    match tokens.next().map(|token| &token.kind) {
+      Some(TokenKind::Numeric(value)) => Some(Expression::Numeric {
+        value: value.to_string(),
+      }),
+      Some(TokenKind::Identifier(name)) => {
+        match tokens.peek().map(|token| &token.kind) {
+          Some(TokenKind::Operator(OperatorSymbol::Assignment)) => {
+            tokens.next();
+            let value = Parser::parse_expression(tokens)?;
+            Some(Expression::Assignment {
+              name: name.to_string(),
+              value: Box::new(value),
+            })
+          }
+          _ => Some(Expression::Identifier {
+            name: name.to_string(),
+          }),
+        }
+      }
+      Some(TokenKind::Pair(PairSymbol::Parentheses, PairType::Open)) => {
+        let expression = Parser::parse_expression(tokens)?;
+        match tokens.next().map(|token| &token.kind) {
+          Some(TokenKind::Pair(PairSymbol::Parentheses, PairType::Close)) => {
+            Some(expression)
+          }
+          _ => panic!("Expected )"),
+        }
+      }
+      _ => None,
+    }
+  }
+
+  /// Maps an operator to its `(left, right)` binding power, used to
+  /// implement precedence climbing in `parse_expression_bp`. Returns `None`
+  /// for operators (like `Assignment`) that aren't parsed as infix binary
+  /// expressions.
+  fn binding_power(
+    operator: &OperatorSymbol,
+  ) -> Option<(BinaryOperator, u8, u8)> {
+    match operator {
+      OperatorSymbol::Equality => Some((BinaryOperator::Equality, 10, 11)),
+      OperatorSymbol::Addition => Some((BinaryOperator::Addition, 20, 21)),
+      OperatorSymbol::Subtraction => {
+        Some((BinaryOperator::Subtraction, 20, 21))
+      }
+      OperatorSymbol::Assignment => None,
+    }
   }
 }
 
@@ -164,6 +390,16 @@ impl Parser {
 mod tests {
   use super::*;
 
+  /// Builds a `Token` with a dummy span, since these tests only care about
+  /// `kind`.
+  fn tok(kind: TokenKind) -> Token {
+    Token {
+      kind,
+      start: 0,
+      len: 0,
+    }
+  }
+
   fn assert_tree(input: Vec<Token>, output: &[Declaration]) {
     let mut parser = Parser::new(input);
     parser.parse();
@@ -172,7 +408,7 @@ mod tests {
   #[test]
   fn test_top_level_comment() {
     assert_tree(
-      vec![Token::Comment(String::from("Hello World"))],
+      vec![tok(TokenKind::Comment(String::from("Hello World")))],
       &[Declaration::Comment {
         text: String::from("Hello World"),
       }],
@@ -183,8 +419,8 @@ mod tests {
   fn test_top_level_comments() {
     assert_tree(
       vec![
-        Token::Comment(String::from("Hello")),
-        Token::Comment(String::from("World")),
+        tok(TokenKind::Comment(String::from("Hello"))),
+        tok(TokenKind::Comment(String::from("World"))),
       ],
       &[Declaration::Comment {
         text: String::from("Hello\nWorld"),
@@ -192,19 +428,275 @@ mod tests {
     );
   }
 
+  fn assert_expression(input: Vec<Token>, expected: Expression) {
+    let mut tokens = input.iter().peekable();
+    let expression = Parser::parse_expression(&mut tokens).unwrap();
+    assert_eq!(expression, expected);
+  }
+
+  #[test]
+  fn test_parse_addition() {
+    assert_expression(
+      vec![
+        tok(TokenKind::Numeric(String::from("1"))),
+        tok(TokenKind::Operator(OperatorSymbol::Addition)),
+        tok(TokenKind::Numeric(String::from("2"))),
+      ],
+      Expression::Binary {
+        left: Box::new(Expression::Numeric {
+          value: String::from("1"),
+        }),
+        right: Box::new(Expression::Numeric {
+          value: String::from("2"),
+        }),
+        operator: BinaryOperator::Addition,
+      },
+    );
+  }
+
+  #[test]
+  fn test_parse_precedence() {
+    // `1 + 2 == 3` should parse as `(1 + 2) == 3`.
+    assert_expression(
+      vec![
+        tok(TokenKind::Numeric(String::from("1"))),
+        tok(TokenKind::Operator(OperatorSymbol::Addition)),
+        tok(TokenKind::Numeric(String::from("2"))),
+        tok(TokenKind::Operator(OperatorSymbol::Equality)),
+        tok(TokenKind::Numeric(String::from("3"))),
+      ],
+      Expression::Binary {
+        left: Box::new(Expression::Binary {
+          left: Box::new(Expression::Numeric {
+            value: String::from("1"),
+          }),
+          right: Box::new(Expression::Numeric {
+            value: String::from("2"),
+          }),
+          operator: BinaryOperator::Addition,
+        }),
+        right: Box::new(Expression::Numeric {
+          value: String::from("3"),
+        }),
+        operator: BinaryOperator::Equality,
+      },
+    );
+  }
+
+  #[test]
+  fn test_parse_grouping() {
+    // `(1 + 2) == 3` groups the addition explicitly; it yields the same tree
+    // as `1 + 2 == 3`, since addition already binds tighter than equality.
+    assert_expression(
+      vec![
+        tok(TokenKind::Pair(PairSymbol::Parentheses, PairType::Open)),
+        tok(TokenKind::Numeric(String::from("1"))),
+        tok(TokenKind::Operator(OperatorSymbol::Addition)),
+        tok(TokenKind::Numeric(String::from("2"))),
+        tok(TokenKind::Pair(PairSymbol::Parentheses, PairType::Close)),
+        tok(TokenKind::Operator(OperatorSymbol::Equality)),
+        tok(TokenKind::Numeric(String::from("3"))),
+      ],
+      Expression::Binary {
+        left: Box::new(Expression::Binary {
+          left: Box::new(Expression::Numeric {
+            value: String::from("1"),
+          }),
+          right: Box::new(Expression::Numeric {
+            value: String::from("2"),
+          }),
+          operator: BinaryOperator::Addition,
+        }),
+        right: Box::new(Expression::Numeric {
+          value: String::from("3"),
+        }),
+        operator: BinaryOperator::Equality,
+      },
+    );
+  }
+
+  #[test]
+  fn test_parse_assignment() {
+    assert_expression(
+      vec![
+        tok(TokenKind::Identifier(String::from("x"))),
+        tok(TokenKind::Operator(OperatorSymbol::Assignment)),
+        tok(TokenKind::Numeric(String::from("1"))),
+      ],
+      Expression::Assignment {
+        name: String::from("x"),
+        value: Box::new(Expression::Numeric {
+          value: String::from("1"),
+        }),
+      },
+    );
+  }
+
+  #[test]
+  fn test_variable_declaration() {
+    assert_tree(
+      vec![
+        tok(TokenKind::Keyword(Keyword::Func)),
+        tok(TokenKind::Identifier(String::from("main"))),
+        tok(TokenKind::Pair(PairSymbol::Parentheses, PairType::Open)),
+        tok(TokenKind::Pair(PairSymbol::Parentheses, PairType::Close)),
+        tok(TokenKind::Pair(PairSymbol::CurlyBracket, PairType::Open)),
+        tok(TokenKind::Keyword(Keyword::Let)),
+        tok(TokenKind::Identifier(String::from("x"))),
+        tok(TokenKind::Operator(OperatorSymbol::Assignment)),
+        tok(TokenKind::Numeric(String::from("1"))),
+        tok(TokenKind::Pair(PairSymbol::CurlyBracket, PairType::Close)),
+      ],
+      &[Declaration::Function {
+        name: String::from("main"),
+        params: Vec::new(),
+        body: vec![Statement::Variable {
+          name: String::from("x"),
+          value: Some(Expression::Numeric {
+            value: String::from("1"),
+          }),
+        }],
+      }],
+    );
+  }
+
   #[test]
   fn test_function_declaration() {
     assert_tree(
       vec![
-        Token::Keyword(Keyword::Func),
-        Token::Identifier(String::from("main")),
-        Token::Pair(PairSymbol::CurlyBracket, PairType::Open),
-        Token::Pair(PairSymbol::CurlyBracket, PairType::Close),
+        tok(TokenKind::Keyword(Keyword::Func)),
+        tok(TokenKind::Identifier(String::from("main"))),
+        tok(TokenKind::Pair(PairSymbol::Parentheses, PairType::Open)),
+        tok(TokenKind::Pair(PairSymbol::Parentheses, PairType::Close)),
+        tok(TokenKind::Pair(PairSymbol::CurlyBracket, PairType::Open)),
+        tok(TokenKind::Pair(PairSymbol::CurlyBracket, PairType::Close)),
       ],
       &[Declaration::Function {
         name: String::from("main"),
+        params: Vec::new(),
         body: Vec::new(),
       }],
     );
   }
+
+  #[test]
+  fn test_function_declaration_with_params() {
+    assert_tree(
+      vec![
+        tok(TokenKind::Keyword(Keyword::Func)),
+        tok(TokenKind::Identifier(String::from("add"))),
+        tok(TokenKind::Pair(PairSymbol::Parentheses, PairType::Open)),
+        tok(TokenKind::Identifier(String::from("a"))),
+        tok(TokenKind::Comma),
+        tok(TokenKind::Identifier(String::from("b"))),
+        tok(TokenKind::Pair(PairSymbol::Parentheses, PairType::Close)),
+        tok(TokenKind::Pair(PairSymbol::CurlyBracket, PairType::Open)),
+        tok(TokenKind::Keyword(Keyword::Return)),
+        tok(TokenKind::Identifier(String::from("a"))),
+        tok(TokenKind::Operator(OperatorSymbol::Addition)),
+        tok(TokenKind::Identifier(String::from("b"))),
+        tok(TokenKind::Pair(PairSymbol::CurlyBracket, PairType::Close)),
+      ],
+      &[Declaration::Function {
+        name: String::from("add"),
+        params: vec![String::from("a"), String::from("b")],
+        body: vec![Statement::Return {
+          value: Some(Expression::Binary {
+            left: Box::new(Expression::Identifier {
+              name: String::from("a"),
+            }),
+            right: Box::new(Expression::Identifier {
+              name: String::from("b"),
+            }),
+            operator: BinaryOperator::Addition,
+          }),
+        }],
+      }],
+    );
+  }
+
+  #[test]
+  fn test_if_statement() {
+    assert_tree(
+      vec![
+        tok(TokenKind::Keyword(Keyword::Func)),
+        tok(TokenKind::Identifier(String::from("main"))),
+        tok(TokenKind::Pair(PairSymbol::Parentheses, PairType::Open)),
+        tok(TokenKind::Pair(PairSymbol::Parentheses, PairType::Close)),
+        tok(TokenKind::Pair(PairSymbol::CurlyBracket, PairType::Open)),
+        tok(TokenKind::Keyword(Keyword::If)),
+        tok(TokenKind::Numeric(String::from("1"))),
+        tok(TokenKind::Operator(OperatorSymbol::Equality)),
+        tok(TokenKind::Numeric(String::from("1"))),
+        tok(TokenKind::Pair(PairSymbol::CurlyBracket, PairType::Open)),
+        tok(TokenKind::Keyword(Keyword::Return)),
+        tok(TokenKind::Pair(PairSymbol::CurlyBracket, PairType::Close)),
+        tok(TokenKind::Pair(PairSymbol::CurlyBracket, PairType::Close)),
+      ],
+      &[Declaration::Function {
+        name: String::from("main"),
+        params: Vec::new(),
+        body: vec![Statement::If {
+          condition: Expression::Binary {
+            left: Box::new(Expression::Numeric {
+              value: String::from("1"),
+            }),
+            right: Box::new(Expression::Numeric {
+              value: String::from("1"),
+            }),
+            operator: BinaryOperator::Equality,
+          },
+          then_body: vec![Statement::Return { value: None }],
+          else_body: None,
+        }],
+      }],
+    );
+  }
+
+  #[test]
+  fn test_if_else_statement() {
+    assert_tree(
+      vec![
+        tok(TokenKind::Keyword(Keyword::Func)),
+        tok(TokenKind::Identifier(String::from("main"))),
+        tok(TokenKind::Pair(PairSymbol::Parentheses, PairType::Open)),
+        tok(TokenKind::Pair(PairSymbol::Parentheses, PairType::Close)),
+        tok(TokenKind::Pair(PairSymbol::CurlyBracket, PairType::Open)),
+        tok(TokenKind::Keyword(Keyword::If)),
+        tok(TokenKind::Numeric(String::from("1"))),
+        tok(TokenKind::Operator(OperatorSymbol::Equality)),
+        tok(TokenKind::Numeric(String::from("1"))),
+        tok(TokenKind::Pair(PairSymbol::CurlyBracket, PairType::Open)),
+        tok(TokenKind::Keyword(Keyword::Return)),
+        tok(TokenKind::Pair(PairSymbol::CurlyBracket, PairType::Close)),
+        tok(TokenKind::Keyword(Keyword::Else)),
+        tok(TokenKind::Pair(PairSymbol::CurlyBracket, PairType::Open)),
+        tok(TokenKind::Keyword(Keyword::Return)),
+        tok(TokenKind::Numeric(String::from("0"))),
+        tok(TokenKind::Pair(PairSymbol::CurlyBracket, PairType::Close)),
+        tok(TokenKind::Pair(PairSymbol::CurlyBracket, PairType::Close)),
+      ],
+      &[Declaration::Function {
+        name: String::from("main"),
+        params: Vec::new(),
+        body: vec![Statement::If {
+          condition: Expression::Binary {
+            left: Box::new(Expression::Numeric {
+              value: String::from("1"),
+            }),
+            right: Box::new(Expression::Numeric {
+              value: String::from("1"),
+            }),
+            operator: BinaryOperator::Equality,
+          },
+          then_body: vec![Statement::Return { value: None }],
+          else_body: Some(vec![Statement::Return {
+            value: Some(Expression::Numeric {
+              value: String::from("0"),
+            }),
+          }]),
+        }],
+      }],
+    );
+  }
 }
@@ -9,13 +9,32 @@ pub struct Scanner {
   pub output: Vec<Token>,
 }
 
-// TODO: Refactor into a struct that retains offset information for debugging.
-// TODO: Add recovery / error tokens.
+/// A single token, tagged with the byte span in the source it came from.
 #[derive(Debug, PartialEq)]
-pub enum Token {
+pub struct Token {
+  /// What kind of token this is, and any data it carries.
+  pub kind: TokenKind,
+
+  /// Byte offset into the scanned input where this token starts.
+  pub start: usize,
+
+  /// Length, in bytes, of the source text this token was scanned from.
+  pub len: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TokenKind {
+  /// Represents a `,`, used to separate items in a list (e.g. parameters).
+  Comma,
+
   /// Represents a single-line comment.
   Comment(String),
 
+  /// A malformed token (e.g. an unterminated string) with a human-readable
+  /// message describing what went wrong. Scanning recovers and continues
+  /// after emitting one of these, rather than aborting.
+  Error(String),
+
   /// Represents a named identifier.
   Identifier(String),
 
@@ -40,8 +59,11 @@ pub enum Token {
 
 #[derive(Debug, PartialEq)]
 pub enum Keyword {
+  Else,
   Func,
+  If,
   Let,
+  Return,
 }
 
 #[derive(Debug, PartialEq)]
@@ -79,9 +101,9 @@ impl Scanner {
   }
 
   pub fn scan(&mut self) {
-    let mut chars = self.input.chars().peekable();
-    while let Some(next) = chars.next() {
-      let token: Option<Token> = match next {
+    let mut chars = self.input.char_indices().peekable();
+    while let Some((start, next)) = chars.next() {
+      let kind: Option<TokenKind> = match next {
         // Identifier or Keywords.
         'a'..='z' | 'A'..='Z' => {
           Scanner::scan_keyword_or_identifier(&mut chars, next)
@@ -94,132 +116,230 @@ impl Scanner {
         '\'' => Scanner::scan_string(&mut chars),
 
         // Operators
-        '+' => Some(Token::Operator(OperatorSymbol::Addition)),
-        '-' => Some(Token::Operator(OperatorSymbol::Subtraction)),
+        '+' => Some(TokenKind::Operator(OperatorSymbol::Addition)),
+        '-' => Some(TokenKind::Operator(OperatorSymbol::Subtraction)),
         '=' => match chars.peek() {
-          Some('=') => {
+          Some((_, '=')) => {
             chars.next();
-            Some(Token::Operator(OperatorSymbol::Equality))
+            Some(TokenKind::Operator(OperatorSymbol::Equality))
           }
-          _ => Some(Token::Operator(OperatorSymbol::Assignment)),
+          _ => Some(TokenKind::Operator(OperatorSymbol::Assignment)),
         },
 
+        // Punctuation.
+        ',' => Some(TokenKind::Comma),
+
         // Pairings.
-        '(' => Some(Token::Pair(PairSymbol::Parentheses, PairType::Open)),
-        ')' => Some(Token::Pair(PairSymbol::Parentheses, PairType::Close)),
-        '{' => Some(Token::Pair(PairSymbol::CurlyBracket, PairType::Open)),
-        '}' => Some(Token::Pair(PairSymbol::CurlyBracket, PairType::Close)),
+        '(' => Some(TokenKind::Pair(PairSymbol::Parentheses, PairType::Open)),
+        ')' => {
+          Some(TokenKind::Pair(PairSymbol::Parentheses, PairType::Close))
+        }
+        '{' => Some(TokenKind::Pair(PairSymbol::CurlyBracket, PairType::Open)),
+        '}' => {
+          Some(TokenKind::Pair(PairSymbol::CurlyBracket, PairType::Close))
+        }
 
         // Comments.
         '/' => Scanner::scan_comment(&mut chars, next),
 
+        // A stray `.` not attached to a leading digit (e.g. the second `.`
+        // in `1.2.3`) is a recoverable error rather than an unknown char.
+        '.' => Some(TokenKind::Error(String::from(
+          "Unexpected '.' outside of a numeric literal",
+        ))),
+
         // Whitespace (Ignore).
         ' ' | '\n' => None,
 
         // Unsupported.
-        _ => Some(Token::Unknown(next)),
+        _ => Some(TokenKind::Unknown(next)),
       };
-      if let Some(token) = token {
-        self.output.push(token);
+      if let Some(kind) = kind {
+        let end = chars.peek().map(|(i, _)| *i).unwrap_or(self.input.len());
+        self.output.push(Token {
+          kind,
+          start,
+          len: end - start,
+        });
       }
     }
   }
 
-  fn scan_comment<T: Iterator<Item = char>>(
+  fn scan_comment<T: Iterator<Item = (usize, char)>>(
     chars: &mut iter::Peekable<T>,
     next: char,
-  ) -> Option<Token> {
-    match chars.peek() {
+  ) -> Option<TokenKind> {
+    match chars.peek().map(|(_, c)| *c) {
       Some('/') => {
         chars.next();
         let mut comment = String::from("");
         loop {
-          let peek = chars.next();
-          match peek {
-            Some('\n') | None => break,
-            _ => comment.push(peek.unwrap().to_owned()),
+          match chars.next() {
+            Some((_, '\n')) | None => break,
+            Some((_, c)) => comment.push(c),
           }
         }
-        Some(Token::Comment(comment))
+        Some(TokenKind::Comment(comment))
       }
-      _ => Some(Token::Unknown(next)),
+      _ => Some(TokenKind::Unknown(next)),
     }
   }
 
-  fn scan_keyword_or_identifier<T: Iterator<Item = char>>(
+  fn scan_keyword_or_identifier<T: Iterator<Item = (usize, char)>>(
     chars: &mut iter::Peekable<T>,
     next: char,
-  ) -> Option<Token> {
+  ) -> Option<TokenKind> {
     let mut name = String::from("");
     let mut current = next;
     loop {
       name.push(current);
-      let peek = chars.peek();
-      match peek {
-        Some('a'..='z') | Some('A'..='Z') => {
-          current = peek.unwrap().to_owned();
+      match chars.peek().map(|(_, c)| *c) {
+        Some(c @ 'a'..='z') | Some(c @ 'A'..='Z') => {
+          current = c;
           chars.next();
         }
         _ => break,
       }
     }
     match name.as_ref() {
-      "func" => Some(Token::Keyword(Keyword::Func)),
-      "let" => Some(Token::Keyword(Keyword::Let)),
-      _ => Some(Token::Identifier(name)),
+      "else" => Some(TokenKind::Keyword(Keyword::Else)),
+      "func" => Some(TokenKind::Keyword(Keyword::Func)),
+      "if" => Some(TokenKind::Keyword(Keyword::If)),
+      "let" => Some(TokenKind::Keyword(Keyword::Let)),
+      "return" => Some(TokenKind::Keyword(Keyword::Return)),
+      _ => Some(TokenKind::Identifier(name)),
     }
   }
 
-  fn scan_number<T: Iterator<Item = char>>(
+  fn scan_number<T: Iterator<Item = (usize, char)>>(
     chars: &mut iter::Peekable<T>,
     next: char,
-  ) -> Option<Token> {
+  ) -> Option<TokenKind> {
+    if next == '0' {
+      match chars.peek().map(|(_, c)| *c) {
+        Some('x') | Some('X') => {
+          chars.next();
+          return Scanner::scan_radix_literal(chars, "0x", |c| {
+            c.is_ascii_hexdigit()
+          });
+        }
+        Some('b') | Some('B') => {
+          chars.next();
+          return Scanner::scan_radix_literal(chars, "0b", |c| {
+            c == '0' || c == '1'
+          });
+        }
+        _ => {}
+      }
+    }
     let mut number = String::from("");
     let mut current = next;
     let mut is_float = false;
+    let mut invalid_underscore = false;
     loop {
-      number.push(current);
-      // TODO: Support numerical seperators (i.e. `_`).
-      // TODO: Support different radix encodings (binary, hex).
-      let peek = chars.peek();
-      match peek {
-        Some('0'..='9') => {
-          current = peek.unwrap().to_owned();
+      // `_` is a visual separator only; strip it from the stored literal.
+      if current != '_' {
+        number.push(current);
+      }
+      // A `_` is only valid directly between two digits: one that follows
+      // or precedes anything else is still consumed, but marks the literal
+      // invalid.
+      let prev_is_digit = current.is_ascii_digit();
+      match chars.peek().map(|(_, c)| *c) {
+        Some(c @ '0'..='9') => {
+          current = c;
+          chars.next();
+        }
+        Some('_') => {
+          if !prev_is_digit {
+            invalid_underscore = true;
+          }
+          current = '_';
           chars.next();
         }
         Some('.') => {
           if is_float {
             break;
           } else {
-            current = peek.unwrap().to_owned();
+            if !prev_is_digit {
+              invalid_underscore = true;
+            }
+            current = '.';
             chars.next();
             is_float = true;
           }
         }
+        _ => {
+          if current == '_' {
+            invalid_underscore = true;
+          }
+          break;
+        }
+      }
+    }
+    if invalid_underscore {
+      Some(TokenKind::Error(format!(
+        "Invalid '_' separator in numeric literal: {}",
+        number
+      )))
+    } else {
+      Some(TokenKind::Numeric(number))
+    }
+  }
+
+  /// Scans the digits of a `0x`/`0b`-prefixed literal (the prefix has
+  /// already been consumed), stripping `_` separators, and returns an error
+  /// token if no valid digit follows the prefix.
+  fn scan_radix_literal<T, F>(
+    chars: &mut iter::Peekable<T>,
+    prefix: &str,
+    is_digit: F,
+  ) -> Option<TokenKind>
+  where
+    T: Iterator<Item = (usize, char)>,
+    F: Fn(char) -> bool,
+  {
+    let mut digits = String::from("");
+    loop {
+      match chars.peek().map(|(_, c)| *c) {
+        Some(c) if is_digit(c) => {
+          digits.push(c);
+          chars.next();
+        }
+        Some('_') if !digits.is_empty() => {
+          chars.next();
+        }
         _ => break,
       }
     }
-    Some(Token::Numeric(number))
+    if digits.is_empty() {
+      Some(TokenKind::Error(format!(
+        "Expected at least one digit after '{}'",
+        prefix
+      )))
+    } else {
+      Some(TokenKind::Numeric(format!("{}{}", prefix, digits)))
+    }
   }
 
-  fn scan_string<T: Iterator<Item = char>>(
+  fn scan_string<T: Iterator<Item = (usize, char)>>(
     chars: &mut iter::Peekable<T>,
-  ) -> Option<Token> {
+  ) -> Option<TokenKind> {
     let mut literal = String::from("");
     loop {
-      let peek = chars.next();
-      match peek {
-        Some('\'') => {
-          chars.next();
-          break;
+      match chars.next() {
+        Some((_, '\'')) => {
+          return Some(TokenKind::String(literal));
         }
-        Some('\n') | None => break,
-        _ => {
-          literal.push(peek.unwrap().to_owned());
+        Some((_, '\n')) | None => {
+          return Some(TokenKind::Error(format!(
+            "Unterminated string literal: '{}",
+            literal
+          )));
         }
+        Some((_, c)) => literal.push(c),
       }
     }
-    Some(Token::String(literal))
   }
 }
 
@@ -227,23 +347,23 @@ impl Scanner {
 mod tests {
   use super::*;
 
-  fn assert_tokens(input: &str, tokens: &[Token]) {
+  fn assert_tokens(input: &str, tokens: &[TokenKind]) {
     let mut scanner = Scanner::new(input.to_string());
     scanner.scan();
     assert_eq!(tokens.len(), scanner.output.len());
-    for (i, token) in tokens.iter().enumerate() {
-      assert_eq!(token, scanner.output.get(i).unwrap());
+    for (i, kind) in tokens.iter().enumerate() {
+      assert_eq!(kind, &scanner.output.get(i).unwrap().kind);
     }
   }
 
   #[test]
   fn test_scan_int_0() {
-    assert_tokens("0", &[Token::Numeric(String::from("0"))]);
+    assert_tokens("0", &[TokenKind::Numeric(String::from("0"))]);
   }
 
   #[test]
   fn test_scan_int_100() {
-    assert_tokens("100", &[Token::Numeric(String::from("100"))]);
+    assert_tokens("100", &[TokenKind::Numeric(String::from("100"))]);
   }
 
   #[test]
@@ -251,16 +371,16 @@ mod tests {
     assert_tokens(
       "10 25 303",
       &[
-        Token::Numeric(String::from("10")),
-        Token::Numeric(String::from("25")),
-        Token::Numeric(String::from("303")),
+        TokenKind::Numeric(String::from("10")),
+        TokenKind::Numeric(String::from("25")),
+        TokenKind::Numeric(String::from("303")),
       ],
     );
   }
 
   #[test]
   fn test_scan_float() {
-    assert_tokens("3.14", &[Token::Numeric(String::from("3.14"))]);
+    assert_tokens("3.14", &[TokenKind::Numeric(String::from("3.14"))]);
   }
 
   #[test]
@@ -268,9 +388,9 @@ mod tests {
     assert_tokens(
       "1.23 2.50 3.03",
       &[
-        Token::Numeric(String::from("1.23")),
-        Token::Numeric(String::from("2.50")),
-        Token::Numeric(String::from("3.03")),
+        TokenKind::Numeric(String::from("1.23")),
+        TokenKind::Numeric(String::from("2.50")),
+        TokenKind::Numeric(String::from("3.03")),
       ],
     );
   }
@@ -280,16 +400,69 @@ mod tests {
     assert_tokens(
       "1.2.3",
       &[
-        Token::Numeric(String::from("1.2")),
-        Token::Unknown('.'),
-        Token::Numeric(String::from("3")),
+        TokenKind::Numeric(String::from("1.2")),
+        TokenKind::Error(String::from(
+          "Unexpected '.' outside of a numeric literal",
+        )),
+        TokenKind::Numeric(String::from("3")),
+      ],
+    );
+  }
+
+  #[test]
+  fn test_scan_hex() {
+    assert_tokens("0xFF", &[TokenKind::Numeric(String::from("0xFF"))]);
+  }
+
+  #[test]
+  fn test_scan_binary() {
+    assert_tokens("0b1010", &[TokenKind::Numeric(String::from("0b1010"))]);
+  }
+
+  #[test]
+  fn test_scan_digit_separators() {
+    assert_tokens(
+      "1_000_000",
+      &[TokenKind::Numeric(String::from("1000000"))],
+    );
+  }
+
+  #[test]
+  fn test_scan_trailing_digit_separator() {
+    assert_tokens(
+      "5_",
+      &[TokenKind::Error(String::from(
+        "Invalid '_' separator in numeric literal: 5",
+      ))],
+    );
+  }
+
+  #[test]
+  fn test_scan_doubled_digit_separator() {
+    assert_tokens(
+      "5__5",
+      &[TokenKind::Error(String::from(
+        "Invalid '_' separator in numeric literal: 55",
+      ))],
+    );
+  }
+
+  #[test]
+  fn test_scan_invalid_radix_prefix() {
+    assert_tokens(
+      "0x_",
+      &[
+        TokenKind::Error(String::from(
+          "Expected at least one digit after '0x'",
+        )),
+        TokenKind::Unknown('_'),
       ],
     );
   }
 
   #[test]
   fn test_scan_identifier() {
-    assert_tokens("foo", &[Token::Identifier(String::from("foo"))]);
+    assert_tokens("foo", &[TokenKind::Identifier(String::from("foo"))]);
   }
 
   #[test]
@@ -297,9 +470,9 @@ mod tests {
     assert_tokens(
       "foo bar baz",
       &[
-        Token::Identifier(String::from("foo")),
-        Token::Identifier(String::from("bar")),
-        Token::Identifier(String::from("baz")),
+        TokenKind::Identifier(String::from("foo")),
+        TokenKind::Identifier(String::from("bar")),
+        TokenKind::Identifier(String::from("baz")),
       ],
     );
   }
@@ -309,10 +482,10 @@ mod tests {
     assert_tokens(
       "foo(bar)",
       &[
-        Token::Identifier(String::from("foo")),
-        Token::Pair(PairSymbol::Parentheses, PairType::Open),
-        Token::Identifier(String::from("bar")),
-        Token::Pair(PairSymbol::Parentheses, PairType::Close),
+        TokenKind::Identifier(String::from("foo")),
+        TokenKind::Pair(PairSymbol::Parentheses, PairType::Open),
+        TokenKind::Identifier(String::from("bar")),
+        TokenKind::Pair(PairSymbol::Parentheses, PairType::Close),
       ],
     );
   }
@@ -322,22 +495,27 @@ mod tests {
     assert_tokens(
       "func A {}",
       &[
-        Token::Keyword(Keyword::Func),
-        Token::Identifier(String::from("A")),
-        Token::Pair(PairSymbol::CurlyBracket, PairType::Open),
-        Token::Pair(PairSymbol::CurlyBracket, PairType::Close),
+        TokenKind::Keyword(Keyword::Func),
+        TokenKind::Identifier(String::from("A")),
+        TokenKind::Pair(PairSymbol::CurlyBracket, PairType::Open),
+        TokenKind::Pair(PairSymbol::CurlyBracket, PairType::Close),
       ],
     );
   }
 
   #[test]
   fn test_scan_string() {
-    assert_tokens("'foo'", &[Token::String(String::from("foo"))]);
+    assert_tokens("'foo'", &[TokenKind::String(String::from("foo"))]);
   }
 
   #[test]
   fn test_scan_string_no_terminator() {
-    assert_tokens("'foo", &[Token::String(String::from("foo"))]);
+    assert_tokens(
+      "'foo",
+      &[TokenKind::Error(String::from(
+        "Unterminated string literal: 'foo",
+      ))],
+    );
   }
 
   #[test]
@@ -345,16 +523,16 @@ mod tests {
     assert_tokens(
       "'foo\nbar'",
       &[
-        Token::String(String::from("foo")),
-        Token::Identifier(String::from("bar")),
-        Token::String(String::from("")),
+        TokenKind::Error(String::from("Unterminated string literal: 'foo")),
+        TokenKind::Identifier(String::from("bar")),
+        TokenKind::Error(String::from("Unterminated string literal: '")),
       ],
     );
   }
 
   #[test]
   fn test_scan_comment() {
-    assert_tokens("// Hello", &[Token::Comment(String::from(" Hello"))])
+    assert_tokens("// Hello", &[TokenKind::Comment(String::from(" Hello"))])
   }
 
   #[test]
@@ -362,8 +540,8 @@ mod tests {
     assert_tokens(
       "// Foo\nbar",
       &[
-        Token::Comment(String::from(" Foo")),
-        Token::Identifier(String::from("bar")),
+        TokenKind::Comment(String::from(" Foo")),
+        TokenKind::Identifier(String::from("bar")),
       ],
     );
   }
@@ -373,9 +551,9 @@ mod tests {
     assert_tokens(
       "1 + 2",
       &[
-        Token::Numeric(String::from("1")),
-        Token::Operator(OperatorSymbol::Addition),
-        Token::Numeric(String::from("2")),
+        TokenKind::Numeric(String::from("1")),
+        TokenKind::Operator(OperatorSymbol::Addition),
+        TokenKind::Numeric(String::from("2")),
       ],
     )
   }
@@ -385,9 +563,9 @@ mod tests {
     assert_tokens(
       "1 - 2",
       &[
-        Token::Numeric(String::from("1")),
-        Token::Operator(OperatorSymbol::Subtraction),
-        Token::Numeric(String::from("2")),
+        TokenKind::Numeric(String::from("1")),
+        TokenKind::Operator(OperatorSymbol::Subtraction),
+        TokenKind::Numeric(String::from("2")),
       ],
     )
   }
@@ -397,23 +575,62 @@ mod tests {
     assert_tokens(
       "1 == 2",
       &[
-        Token::Numeric(String::from("1")),
-        Token::Operator(OperatorSymbol::Equality),
-        Token::Numeric(String::from("2")),
+        TokenKind::Numeric(String::from("1")),
+        TokenKind::Operator(OperatorSymbol::Equality),
+        TokenKind::Numeric(String::from("2")),
       ],
     )
   }
 
+  #[test]
+  fn test_scan_comma() {
+    assert_tokens(
+      "a, b",
+      &[
+        TokenKind::Identifier(String::from("a")),
+        TokenKind::Comma,
+        TokenKind::Identifier(String::from("b")),
+      ],
+    )
+  }
+
+  #[test]
+  fn test_scan_if() {
+    assert_tokens("if", &[TokenKind::Keyword(Keyword::If)]);
+  }
+
+  #[test]
+  fn test_scan_return() {
+    assert_tokens("return", &[TokenKind::Keyword(Keyword::Return)]);
+  }
+
+  #[test]
+  fn test_scan_else() {
+    assert_tokens("else", &[TokenKind::Keyword(Keyword::Else)]);
+  }
+
   #[test]
   fn test_scan_assignment() {
     assert_tokens(
       "let x = 1",
       &[
-        Token::Keyword(Keyword::Let),
-        Token::Identifier(String::from("x")),
-        Token::Operator(OperatorSymbol::Assignment),
-        Token::Numeric(String::from("1")),
+        TokenKind::Keyword(Keyword::Let),
+        TokenKind::Identifier(String::from("x")),
+        TokenKind::Operator(OperatorSymbol::Assignment),
+        TokenKind::Numeric(String::from("1")),
       ],
     )
   }
+
+  #[test]
+  fn test_scan_spans() {
+    let mut scanner = Scanner::new(String::from("let x = 1"));
+    scanner.scan();
+    let spans: Vec<(usize, usize)> = scanner
+      .output
+      .iter()
+      .map(|token| (token.start, token.len))
+      .collect();
+    assert_eq!(spans, vec![(0, 3), (4, 1), (6, 1), (8, 1)]);
+  }
 }